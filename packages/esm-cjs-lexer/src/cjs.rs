@@ -928,6 +928,12 @@ impl ExportsParser {
     self.exports = dep_parser.exports;
     self.reexports = dep_parser.reexports;
   }
+
+  // parse the top-level statements of a module or script
+  fn parse_top_level(&mut self, stmts: Vec<Stmt>) {
+    let stmts = stmts.into_iter().filter(|stmt| !stmt.is_empty()).collect::<Vec<Stmt>>();
+    self.parse(stmts, false);
+  }
 }
 
 impl Fold for ExportsParser {
@@ -944,11 +950,15 @@ impl Fold for ExportsParser {
         ModuleItem::Stmt(stmt) => stmt.clone(),
         _ => Stmt::Empty(EmptyStmt { span: DUMMY_SP }),
       })
-      .filter(|item| !item.is_empty())
       .collect::<Vec<Stmt>>();
-    self.parse(stmts, false);
+    self.parse_top_level(stmts);
     items
   }
+
+  fn fold_script(&mut self, script: Script) -> Script {
+    self.parse_top_level(script.body.clone());
+    script
+  }
 }
 
 fn is_module_ident(expr: &Expr) -> bool {
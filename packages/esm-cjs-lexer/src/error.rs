@@ -26,13 +26,32 @@ impl Emitter for ErrorBuffer {
   }
 }
 
+/// A diagnostic message from the AST parser.
+#[derive(Debug, Clone)]
+pub struct DiagnosticMessage {
+  pub message: String,
+  /// The 1-based line and column of the primary span.
+  pub location: Option<(usize, usize)>,
+}
+
 /// A buffer for collecting diagnostic messages from the AST parser.
 #[derive(Debug)]
-pub struct DiagnosticBuffer(Vec<String>);
+pub struct DiagnosticBuffer {
+  specifier: String,
+  diagnostics: Vec<DiagnosticMessage>,
+}
 
 impl fmt::Display for DiagnosticBuffer {
   fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-    fmt.pad(&self.0.join(","))
+    let messages: Vec<String> = self
+      .diagnostics
+      .iter()
+      .map(|d| match d.location {
+        Some((line, column)) => format!("{} at {}:{}:{}", d.message, self.specifier, line, column),
+        None => d.message.to_owned(),
+      })
+      .collect();
+    fmt.pad(&messages.join(","))
   }
 }
 
@@ -44,19 +63,45 @@ impl DiagnosticBuffer {
     let diagnostics = error_buffer.diagnostics.read().unwrap().clone();
     let diagnostics = diagnostics
       .iter()
-      .map(|d| {
-        let mut message = d.message();
-        if let Some(span) = d.span.primary_span() {
+      .map(|d| DiagnosticMessage {
+        message: d.message(),
+        location: d.span.primary_span().map(|span| {
           let loc = get_loc(span);
-          message = format!(
-            "{} at {}:{}:{}",
-            message, error_buffer.specifier, loc.line, loc.col_display
-          );
-        }
-        message
+          (loc.line, loc.col.0 + 1)
+        }),
       })
       .collect();
 
-    Self(diagnostics)
+    Self {
+      specifier: error_buffer.specifier,
+      diagnostics,
+    }
+  }
+
+  /// Returns the specifier of the parsed source code.
+  pub fn specifier(&self) -> &str {
+    &self.specifier
   }
+
+  /// Returns the collected diagnostic messages.
+  pub fn diagnostics(&self) -> &[DiagnosticMessage] {
+    &self.diagnostics
+  }
+}
+
+/// An error returned by the lexer.
+#[derive(Debug)]
+pub enum LexerError {
+  /// The source code could not be parsed.
+  Parse { diagnostics: DiagnosticBuffer },
 }
+
+impl fmt::Display for LexerError {
+  fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      LexerError::Parse { diagnostics } => fmt::Display::fmt(diagnostics, fmt),
+    }
+  }
+}
+
+impl std::error::Error for LexerError {}
@@ -0,0 +1,249 @@
+use indexmap::IndexSet;
+use swc_ecma_ast::*;
+use swc_ecma_visit::{noop_visit_type, Visit, VisitWith};
+
+/// The exports of an es module.
+#[derive(Clone, Debug, Default)]
+pub struct Exports {
+  /// the named exports, including the namespace re-exports like `export * as ns from 'lib'`.
+  pub names: Vec<String>,
+  /// whether there is a default export, including `export { foo as default }`.
+  pub has_default: bool,
+  /// the specifiers of the star re-exports like `export * from 'lib'`.
+  pub star_reexports: Vec<String>,
+}
+
+/// collect the export names in the es module.
+pub fn collect_exports(module: &Module) -> Exports {
+  let mut names: IndexSet<String> = IndexSet::new();
+  let mut star_reexports: IndexSet<String> = IndexSet::new();
+  let mut has_default = false;
+
+  for item in &module.body {
+    if let ModuleItem::ModuleDecl(decl) = item {
+      match decl {
+        // export const foo = 'bar'
+        // export function foo() {}
+        // export class Foo {}
+        ModuleDecl::ExportDecl(ExportDecl { decl, .. }) => match decl {
+          Decl::Var(VarDecl { decls, .. }) => {
+            for decl in decls {
+              get_pat_names(&decl.name, &mut names);
+            }
+          }
+          Decl::Fn(FnDecl { ident, .. }) => {
+            names.insert(ident.sym.as_ref().into());
+          }
+          Decl::Class(ClassDecl { ident, .. }) => {
+            names.insert(ident.sym.as_ref().into());
+          }
+          _ => {}
+        },
+        // export { foo, bar as baz }
+        // export { foo as default }
+        // export * as ns from 'lib'
+        ModuleDecl::ExportNamed(NamedExport {
+          specifiers, type_only, ..
+        }) => {
+          if *type_only {
+            continue;
+          }
+          for specifier in specifiers {
+            let name = match specifier {
+              ExportSpecifier::Named(ExportNamedSpecifier {
+                orig,
+                exported,
+                is_type_only,
+                ..
+              }) => {
+                if *is_type_only {
+                  continue;
+                }
+                stringify_export_name(exported.as_ref().unwrap_or(orig))
+              }
+              ExportSpecifier::Namespace(ExportNamespaceSpecifier { name, .. }) => name.sym.as_ref().into(),
+              ExportSpecifier::Default(ExportDefaultSpecifier { exported }) => exported.sym.as_ref().into(),
+            };
+            if name.eq("default") {
+              has_default = true;
+            } else {
+              names.insert(name);
+            }
+          }
+        }
+        // export default foo
+        // export default function () {}
+        ModuleDecl::ExportDefaultDecl(_) | ModuleDecl::ExportDefaultExpr(_) => {
+          has_default = true;
+        }
+        // export * from 'lib'
+        ModuleDecl::ExportAll(ExportAll { src, .. }) => {
+          star_reexports.insert(src.value.as_ref().into());
+        }
+        _ => {}
+      }
+    }
+  }
+
+  Exports {
+    names: names.into_iter().collect(),
+    has_default,
+    star_reexports: star_reexports.into_iter().collect(),
+  }
+}
+
+/// scan the imported and exported module specifiers, returns each specifier with an `is_dynamic` flag.
+pub fn scan_dependencies(program: &Program) -> Vec<(String, bool)> {
+  let mut scanner = DependencyScanner { deps: IndexSet::new() };
+  program.visit_with(&mut scanner);
+  scanner.deps.into_iter().collect()
+}
+
+struct DependencyScanner {
+  deps: IndexSet<(String, bool)>,
+}
+
+impl Visit for DependencyScanner {
+  noop_visit_type!();
+
+  // import foo from 'lib'
+  // export { foo } from 'lib'
+  // export * from 'lib'
+  fn visit_module_decl(&mut self, decl: &ModuleDecl) {
+    let src = match decl {
+      ModuleDecl::Import(ImportDecl { src, type_only, .. }) => Some(src).filter(|_| !type_only),
+      ModuleDecl::ExportNamed(NamedExport { src, type_only, .. }) => src.as_ref().filter(|_| !type_only),
+      ModuleDecl::ExportAll(ExportAll { src, .. }) => Some(src),
+      _ => None,
+    };
+    if let Some(Str { value, .. }) = src {
+      self.deps.insert((value.as_ref().into(), false));
+    }
+    decl.visit_children_with(self);
+  }
+
+  // import('lib')
+  // import(`lib`)
+  fn visit_call_expr(&mut self, call: &CallExpr) {
+    if let ExprOrSuper::Expr(callee) = &call.callee {
+      if let Expr::Ident(id) = callee.as_ref() {
+        if id.sym.as_ref().eq("import") {
+          let src = match call.args.first().map(|arg| arg.expr.as_ref()) {
+            Some(Expr::Lit(Lit::Str(Str { value, .. }))) => Some(value),
+            Some(Expr::Tpl(Tpl { exprs, quasis, .. })) if exprs.is_empty() => quasis
+              .first()
+              .and_then(|quasi| quasi.cooked.as_ref())
+              .map(|cooked| &cooked.value),
+            _ => None,
+          };
+          if let Some(value) = src {
+            self.deps.insert((value.as_ref().into(), true));
+          }
+        }
+      }
+    }
+    call.visit_children_with(self);
+  }
+}
+
+/// checks whether there is an `await` outside of any function, e.g. `await import('./mod')` or `for await (...)`.
+pub fn has_top_level_await(program: &Program) -> bool {
+  let finder = find_top_level_await(program);
+  // `await` is an identifier if it's declared, e.g. `function await() {}; await(1)` in a classic script
+  finder.found && !finder.await_declared
+}
+
+/// finds the `await` expressions and declarations outside of any function.
+pub fn find_top_level_await(program: &Program) -> TopLevelAwaitFinder {
+  let mut finder = TopLevelAwaitFinder {
+    found: false,
+    await_declared: false,
+  };
+  program.visit_with(&mut finder);
+  finder
+}
+
+pub struct TopLevelAwaitFinder {
+  /// whether there is an `await` expression or a `for await` loop.
+  pub found: bool,
+  /// whether `await` is declared as an identifier, e.g. `var await = 1`.
+  pub await_declared: bool,
+}
+
+impl Visit for TopLevelAwaitFinder {
+  noop_visit_type!();
+
+  fn visit_await_expr(&mut self, _: &AwaitExpr) {
+    self.found = true;
+  }
+
+  fn visit_for_of_stmt(&mut self, stmt: &ForOfStmt) {
+    if stmt.await_token.is_some() {
+      self.found = true;
+    } else {
+      stmt.visit_children_with(self);
+    }
+  }
+
+  // var await = 1
+  fn visit_binding_ident(&mut self, id: &BindingIdent) {
+    if id.id.sym.as_ref().eq("await") {
+      self.await_declared = true;
+    }
+  }
+
+  // function await() {}
+  fn visit_fn_decl(&mut self, decl: &FnDecl) {
+    if decl.ident.sym.as_ref().eq("await") {
+      self.await_declared = true;
+    }
+    decl.visit_children_with(self);
+  }
+
+  // class await {}
+  fn visit_class_decl(&mut self, decl: &ClassDecl) {
+    if decl.ident.sym.as_ref().eq("await") {
+      self.await_declared = true;
+    }
+    decl.visit_children_with(self);
+  }
+
+  // skip function bodies
+  fn visit_function(&mut self, _: &Function) {}
+  fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+}
+
+// const { a, b: [c, ...d], e = 1 } = obj
+fn get_pat_names(pat: &Pat, names: &mut IndexSet<String>) {
+  match pat {
+    Pat::Ident(BindingIdent { id, .. }) => {
+      names.insert(id.sym.as_ref().into());
+    }
+    Pat::Array(ArrayPat { elems, .. }) => {
+      for elem in elems.iter().flatten() {
+        get_pat_names(elem, names);
+      }
+    }
+    Pat::Object(ObjectPat { props, .. }) => {
+      for prop in props {
+        match prop {
+          ObjectPatProp::KeyValue(KeyValuePatProp { value, .. }) => get_pat_names(value, names),
+          ObjectPatProp::Assign(AssignPatProp { key, .. }) => {
+            names.insert(key.sym.as_ref().into());
+          }
+          ObjectPatProp::Rest(RestPat { arg, .. }) => get_pat_names(arg, names),
+        }
+      }
+    }
+    Pat::Rest(RestPat { arg, .. }) => get_pat_names(arg, names),
+    Pat::Assign(AssignPat { left, .. }) => get_pat_names(left, names),
+    _ => {}
+  }
+}
+
+fn stringify_export_name(name: &ModuleExportName) -> String {
+  match name {
+    ModuleExportName::Ident(id) => id.sym.as_ref().into(),
+    ModuleExportName::Str(Str { value, .. }) => value.as_ref().into(),
+  }
+}
@@ -1,12 +1,16 @@
 mod cjs;
 mod error;
+mod esm;
 mod swc;
 mod test;
 
 use serde::Serialize;
-use swc::SWC;
 use wasm_bindgen::prelude::{wasm_bindgen, JsValue};
 
+pub use error::{DiagnosticBuffer, DiagnosticMessage, LexerError};
+pub use esm::Exports;
+pub use swc::{ParseMode, SWC};
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Output {
@@ -20,21 +24,30 @@ pub fn parse(
   code: &str,
   node_env: Option<String>,
   call_mode: Option<bool>,
+  parse_mode: Option<String>,
 ) -> Result<JsValue, JsValue> {
   console_error_panic_hook::set_once();
 
-  let swc = SWC::parse(specifier, code).expect("could not parse module");
+  let parse_mode = match parse_mode.as_deref() {
+    None | Some("module") => ParseMode::Module,
+    Some("script") => ParseMode::Script,
+    Some("auto") => ParseMode::Auto,
+    Some(mode) => return Err(JsValue::from_str(&format!("unknown parse mode '{}'", mode))),
+  };
+  let swc = SWC::parse_as(specifier, code, parse_mode).map_err(|err| JsValue::from_str(&err.to_string()))?;
   let node_env = if let Some(env) = node_env {
     env
   } else {
     "production".to_owned()
   };
   let call_mode = if let Some(ok) = call_mode { ok } else { false };
-  let (exports, reexports) = swc.parse_cjs_exports(node_env.as_str(), call_mode).unwrap();
+  let (exports, reexports) = swc
+    .parse_cjs_exports(node_env.as_str(), call_mode)
+    .map_err(|err| JsValue::from_str(&err.to_string()))?;
   let output = &Output {
     exports: exports,
     reexports: reexports,
   };
 
-  Ok(JsValue::from_serde(output).unwrap())
+  JsValue::from_serde(output).map_err(|err| JsValue::from_str(&err.to_string()))
 }
@@ -1,39 +1,72 @@
 use crate::cjs::ExportsParser;
-use crate::error::{DiagnosticBuffer, ErrorBuffer};
+use crate::error::{DiagnosticBuffer, DiagnosticMessage, ErrorBuffer, LexerError};
+use crate::esm::{collect_exports, find_top_level_await, has_top_level_await, scan_dependencies, Exports};
 
 use indexmap::{IndexMap, IndexSet};
 use std::{path::Path, rc::Rc};
 use swc_common::{
   comments::SingleThreadedComments,
   errors::{Handler, HandlerFlags},
-  FileName, SourceMap,
+  FileName, SourceFile, SourceMap,
 };
 use swc_ecma_ast::EsVersion;
 use swc_ecmascript::{
-  ast::{Module, Program},
-  parser::{lexer::Lexer, EsConfig, StringInput, Syntax},
+  ast::Program,
+  parser::{error::Error, lexer::Lexer, EsConfig, PResult, Parser, StringInput, Syntax},
   visit::FoldWith,
 };
 
+/// How the source code is parsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseMode {
+  /// parse as an ES module.
+  Module,
+  /// parse as a classic script(sloppy mode).
+  Script,
+  /// parse as an ES module if it contains `import`/`export` or top-level `await`, otherwise as a script.
+  Auto,
+}
+
 pub struct SWC {
   pub specifier: String,
-  pub module: Module,
+  pub program: Program,
   pub source_map: Rc<SourceMap>,
   pub comments: SingleThreadedComments,
+  /// The recoverable errors reported while parsing.
+  pub diagnostics: Vec<DiagnosticMessage>,
 }
 
 impl SWC {
   /// parse source code.
-  pub fn parse(specifier: &str, source: &str) -> Result<Self, anyhow::Error> {
-    let source_map = SourceMap::default();
+  pub fn parse(specifier: &str, source: &str) -> Result<Self, LexerError> {
+    Self::parse_into(Rc::new(SourceMap::default()), specifier, source)
+  }
+
+  /// parse source code into the given source map, which can be shared across multiple parses.
+  pub fn parse_into(source_map: Rc<SourceMap>, specifier: &str, source: &str) -> Result<Self, LexerError> {
+    Self::parse_as_into(source_map, specifier, source, ParseMode::Module)
+  }
+
+  /// parse source code as a classic script(sloppy mode), e.g. IIFE bundles or UMD globals.
+  pub fn parse_script(specifier: &str, source: &str) -> Result<Self, LexerError> {
+    Self::parse_as_into(Rc::new(SourceMap::default()), specifier, source, ParseMode::Script)
+  }
+
+  /// parse source code with the given parse mode.
+  pub fn parse_as(specifier: &str, source: &str, mode: ParseMode) -> Result<Self, LexerError> {
+    Self::parse_as_into(Rc::new(SourceMap::default()), specifier, source, mode)
+  }
+
+  /// parse source code with the given parse mode into the given source map.
+  pub fn parse_as_into(
+    source_map: Rc<SourceMap>,
+    specifier: &str,
+    source: &str,
+    mode: ParseMode,
+  ) -> Result<Self, LexerError> {
     let source_file = source_map.new_source_file(FileName::Real(Path::new(specifier).to_path_buf()), source.into());
     let sm = &source_map;
     let error_buffer = ErrorBuffer::new(specifier);
-    let syntax = Syntax::Es(get_es_config());
-    let input = StringInput::from(&*source_file);
-    let comments = SingleThreadedComments::default();
-    let lexer = Lexer::new(syntax, EsVersion::Es2020, input, Some(&comments));
-    let mut parser = swc_ecmascript::parser::Parser::new_from(lexer);
     let handler = Handler::with_emitter_and_flags(
       Box::new(error_buffer.clone()),
       HandlerFlags {
@@ -42,29 +75,60 @@ impl SWC {
         ..HandlerFlags::default()
       },
     );
-    let module = parser
-      .parse_module()
-      .map_err(move |err| {
-        let mut diagnostic = err.into_diagnostic(&handler);
-        diagnostic.emit();
-        DiagnosticBuffer::from_error_buffer(error_buffer, |span| sm.lookup_char_pos(span.lo))
-      })
-      .unwrap();
+    let mut comments = SingleThreadedComments::default();
+    let (program, errors) = parse_with_mode(&source_file, &comments, mode);
+    // `parse_program` only treats a script as a module if one of its statements starts with `await`, and doesn't
+    // know `await` can be declared as an identifier in a classic script, so check the top-level `await` again.
+    let reparse_mode = match &program {
+      Ok(program) if mode == ParseMode::Auto => {
+        let has_module_decl = match program {
+          Program::Module(module) => module.body.iter().any(|item| item.is_module_decl()),
+          Program::Script(_) => false,
+        };
+        let finder = find_top_level_await(program);
+        if program.is_script() && finder.found && !finder.await_declared {
+          Some(ParseMode::Module)
+        } else if program.is_module() && !has_module_decl && finder.await_declared {
+          Some(ParseMode::Script)
+        } else {
+          None
+        }
+      }
+      _ => None,
+    };
+    let (program, errors) = match reparse_mode {
+      Some(mode) => {
+        comments = SingleThreadedComments::default();
+        parse_with_mode(&source_file, &comments, mode)
+      }
+      None => (program, errors),
+    };
+    // emit the recoverable errors, which are reported even if the parsing succeeds
+    for err in errors {
+      err.into_diagnostic(&handler).emit();
+    }
+    let program = match program {
+      Ok(program) => program,
+      Err(err) => {
+        err.into_diagnostic(&handler).emit();
+        return Err(LexerError::Parse {
+          diagnostics: DiagnosticBuffer::from_error_buffer(error_buffer, |span| sm.lookup_char_pos(span.lo)),
+        });
+      }
+    };
+    let diagnostics = DiagnosticBuffer::from_error_buffer(error_buffer, |span| sm.lookup_char_pos(span.lo));
 
     Ok(SWC {
       specifier: specifier.into(),
-      module,
-      source_map: Rc::new(source_map),
+      program,
+      source_map,
       comments,
+      diagnostics: diagnostics.diagnostics().to_vec(),
     })
   }
 
   /// parse export names in the cjs module.
-  pub fn parse_cjs_exports(
-    &self,
-    node_env: &str,
-    call_mode: bool,
-  ) -> Result<(Vec<String>, Vec<String>), anyhow::Error> {
+  pub fn parse_cjs_exports(&self, node_env: &str, call_mode: bool) -> Result<(Vec<String>, Vec<String>), LexerError> {
     let mut parser = ExportsParser {
       node_env: node_env.to_owned(),
       call_mode: call_mode,
@@ -74,19 +138,61 @@ impl SWC {
       exports: IndexSet::new(),
       reexports: IndexSet::new(),
     };
-    let program = Program::Module(self.module.clone());
-    program.fold_with(&mut parser);
+    self.program.clone().fold_with(&mut parser);
     Ok((
       parser.exports.into_iter().collect(),
       parser.reexports.into_iter().collect(),
     ))
   }
+
+  /// collect export names in the es module.
+  pub fn collect_exports(&self) -> Exports {
+    match &self.program {
+      Program::Module(module) => collect_exports(module),
+      Program::Script(_) => Exports::default(),
+    }
+  }
+
+  /// scan the imported and exported module specifiers, returns each specifier with an `is_dynamic` flag.
+  pub fn scan_dependencies(&self) -> Vec<(String, bool)> {
+    scan_dependencies(&self.program)
+  }
+
+  /// checks whether the program has an `await` outside of any function.
+  pub fn has_top_level_await(&self) -> bool {
+    has_top_level_await(&self.program)
+  }
+
+  /// serialize the program AST(including spans) to JSON.
+  ///
+  /// The spans are absolute `BytePos` offsets in the source map rather than offsets in this file, which differ when
+  /// the source map is shared via `parse_into`.
+  pub fn module_to_json(&self) -> Result<String, serde_json::Error> {
+    serde_json::to_string(&self.program)
+  }
+}
+
+fn parse_with_mode(
+  source_file: &SourceFile,
+  comments: &SingleThreadedComments,
+  mode: ParseMode,
+) -> (PResult<Program>, Vec<Error>) {
+  let input = StringInput::from(source_file);
+  let lexer = Lexer::new(Syntax::Es(get_es_config()), EsVersion::Es2020, input, Some(comments));
+  let mut parser = Parser::new_from(lexer);
+  let program = match mode {
+    ParseMode::Module => parser.parse_module().map(Program::Module),
+    ParseMode::Script => parser.parse_script().map(Program::Script),
+    ParseMode::Auto => parser.parse_program(),
+  };
+  (program, parser.take_errors())
 }
 
 fn get_es_config() -> EsConfig {
   EsConfig {
     export_default_from: false,
     import_assertions: false,
+    static_blocks: true,
     jsx: false,
     ..EsConfig::default()
   }
@@ -1,6 +1,10 @@
 #[cfg(test)]
 mod tests {
-  use crate::swc::SWC;
+  use crate::error::LexerError;
+  use crate::swc::{ParseMode, SWC};
+  use std::rc::Rc;
+  use swc_common::SourceMap;
+  use swc_ecma_ast::Program;
 
   #[test]
   fn parse_cjs_exports_case_1() {
@@ -694,4 +698,317 @@ mod tests {
       .expect("could not parse exports");
     assert_eq!(exports.join(","), "i18n,use,t");
   }
+
+  #[test]
+  fn parse_error() {
+    let source = r#"
+      exports.foo = 'bar';
+      exports.bar = ;
+		"#;
+    let err = SWC::parse("index.cjs", source).err().expect("should not parse module");
+    assert!(err.to_string().ends_with("at index.cjs:3:21"), "{}", err);
+    let LexerError::Parse { diagnostics } = err;
+    assert_eq!(diagnostics.specifier(), "index.cjs");
+    assert_eq!(diagnostics.diagnostics().len(), 1);
+    assert_eq!(diagnostics.diagnostics()[0].location, Some((3, 21)));
+  }
+
+  #[test]
+  fn parse_error_with_wide_chars_and_tabs() {
+    let err = SWC::parse("index.cjs", "var s = '中文中文'; exports.a = ;")
+      .err()
+      .expect("should not parse module");
+    assert!(err.to_string().ends_with("at index.cjs:1:29"), "{}", err);
+    let err = SWC::parse("index.cjs", "\tx = ;")
+      .err()
+      .expect("should not parse module");
+    assert!(err.to_string().ends_with("at index.cjs:1:6"), "{}", err);
+  }
+
+  #[test]
+  fn parse_error_with_all_diagnostics() {
+    let source = r#"
+      with (obj) {}
+      exports.bar = ;
+		"#;
+    let err = SWC::parse("index.cjs", source).err().expect("should not parse module");
+    let message = err.to_string();
+    assert!(
+      message.contains("With statement are not allowed in strict mode at index.cjs:2:7"),
+      "{}",
+      message
+    );
+    assert!(message.ends_with("at index.cjs:3:21"), "{}", message);
+  }
+
+  #[test]
+  fn parse_with_recoverable_error() {
+    let swc = SWC::parse("index.cjs", "with (obj) {}\nexports.a = 1;").expect("could not parse module");
+    assert_eq!(swc.diagnostics.len(), 1);
+    let message = &swc.diagnostics[0].message;
+    assert!(message.contains("With statement"), "{}", message);
+    assert_eq!(swc.diagnostics[0].location, Some((1, 1)));
+    let (exports, _) = swc
+      .parse_cjs_exports("production", false)
+      .expect("could not parse exports");
+    assert_eq!(exports.join(","), "a");
+    let swc = SWC::parse("index.cjs", "exports.a = 1;").expect("could not parse module");
+    assert_eq!(swc.diagnostics.len(), 0);
+  }
+
+  #[test]
+  fn collect_esm_exports() {
+    let source = r#"
+      const a = 1, b = 2;
+      export { a, b as c };
+      export const d = 1, { e, f: [g, ...h] } = obj;
+      export function i() {}
+      export class J {}
+      export * as k from "./k";
+      export * from "./lib";
+      export * from "./lib2";
+    "#;
+    let swc = SWC::parse("index.mjs", source).expect("could not parse module");
+    let exports = swc.collect_exports();
+    assert_eq!(exports.names.join(","), "a,c,d,e,g,h,i,J,k");
+    assert!(!exports.has_default);
+    assert_eq!(exports.star_reexports.join(","), "./lib,./lib2");
+  }
+
+  #[test]
+  fn collect_esm_exports_with_default() {
+    let swc = SWC::parse("index.mjs", "export default function () {}").expect("could not parse module");
+    assert!(swc.collect_exports().has_default);
+    let swc = SWC::parse("index.mjs", "export default 123").expect("could not parse module");
+    assert!(swc.collect_exports().has_default);
+    let swc = SWC::parse("index.mjs", "const foo = 1; export { foo as default }").expect("could not parse module");
+    let exports = swc.collect_exports();
+    assert!(exports.has_default);
+    assert_eq!(exports.names.len(), 0);
+    let swc = SWC::parse("index.mjs", "export { default } from './lib'").expect("could not parse module");
+    assert!(swc.collect_exports().has_default);
+    let swc = SWC::parse("index.mjs", "export { foo as default } from './lib'").expect("could not parse module");
+    assert!(swc.collect_exports().has_default);
+    let swc = SWC::parse("index.mjs", "export * from './lib'").expect("could not parse module");
+    assert!(!swc.collect_exports().has_default);
+  }
+
+  #[test]
+  fn parse_cjs_exports_with_shebang() {
+    let source = "#!/usr/bin/env node\nexports.foo = 'bar';\n";
+    let swc = SWC::parse("cli.cjs", source).expect("could not parse module");
+    if let Program::Module(module) = &swc.program {
+      assert_eq!(module.shebang.as_ref().map(|s| s.as_ref()), Some("/usr/bin/env node"));
+    } else {
+      panic!("should be a module");
+    }
+    let (exports, _) = swc
+      .parse_cjs_exports("production", false)
+      .expect("could not parse exports");
+    assert_eq!(exports.join(","), "foo");
+  }
+
+  #[test]
+  fn parse_cjs_exports_with_bom() {
+    let source = "\u{feff}exports.bar = ;\n";
+    let err = SWC::parse("index.cjs", source).err().expect("should not parse module");
+    assert!(err.to_string().ends_with("at index.cjs:1:15"), "{}", err);
+    let source = "\u{feff}exports.foo = 'bar';\n";
+    let swc = SWC::parse("index.cjs", source).expect("could not parse module");
+    let (exports, _) = swc
+      .parse_cjs_exports("production", false)
+      .expect("could not parse exports");
+    assert_eq!(exports.join(","), "foo");
+  }
+
+  #[test]
+  fn parse_into_shared_source_map() {
+    let source_map = Rc::new(SourceMap::default());
+    let a = SWC::parse_into(source_map.clone(), "a.cjs", "exports.a = 1;").expect("could not parse module");
+    let b = SWC::parse_into(source_map.clone(), "b.cjs", "exports.b = 1;").expect("could not parse module");
+    assert!(Rc::ptr_eq(&a.source_map, &b.source_map));
+    assert_eq!(source_map.files().len(), 2);
+    let err = SWC::parse_into(source_map.clone(), "c.cjs", "\nexports.c = ;")
+      .err()
+      .expect("should not parse module");
+    assert!(err.to_string().ends_with("at c.cjs:2:13"), "{}", err);
+    let (exports, _) = b
+      .parse_cjs_exports("production", false)
+      .expect("could not parse exports");
+    assert_eq!(exports.join(","), "b");
+    let d = SWC::parse_as_into(
+      source_map.clone(),
+      "d.js",
+      "var await = 1; exports.d = 1;",
+      ParseMode::Script,
+    )
+    .expect("could not parse script");
+    assert!(d.program.is_script());
+    assert!(Rc::ptr_eq(&a.source_map, &d.source_map));
+    assert_eq!(source_map.files().len(), 4);
+  }
+
+  #[test]
+  fn parse_cjs_exports_as_script() {
+    let source = r#"
+      var await = { bar: 1 };
+      with (await) {
+        exports.foo = bar;
+      }
+      exports.baz = 1;
+		"#;
+    assert!(SWC::parse("index.cjs", source).is_err());
+    let swc = SWC::parse_script("index.cjs", source).expect("could not parse script");
+    assert!(swc.program.is_script());
+    let (exports, _) = swc
+      .parse_cjs_exports("production", false)
+      .expect("could not parse exports");
+    assert_eq!(exports.join(","), "baz");
+    assert_eq!(swc.collect_exports().names.len(), 0);
+  }
+
+  #[test]
+  fn parse_cjs_exports_as_script_readme_example() {
+    let source = r#"
+  var await = { bar: 1 };
+  with (await) {
+    console.log(bar);
+  }
+  exports.foo = (await);
+"#;
+    let swc = SWC::parse_script("index.js", source).expect("could not parse script");
+    assert_eq!(swc.diagnostics.len(), 0);
+    let (exports, _) = swc
+      .parse_cjs_exports("production", false)
+      .expect("could not parse exports");
+    assert_eq!(exports.join(","), "foo");
+  }
+
+  #[test]
+  fn scan_esm_dependencies() {
+    let source = r#"
+      import React from "react";
+      import { useState } from "react";
+      import "./style.css";
+      export { foo } from "./foo";
+      export * from "./lib";
+      const bar = 1;
+      export { bar };
+      export async function load() {
+        const { default: mod } = await import("./mod");
+        await import(`./static.js`);
+        await import(`./locales/${lang}.js`);
+        return mod;
+      }
+    "#;
+    let swc = SWC::parse("index.mjs", source).expect("could not parse module");
+    let deps = swc
+      .scan_dependencies()
+      .into_iter()
+      .map(|(specifier, is_dynamic)| format!("{}:{}", specifier, is_dynamic))
+      .collect::<Vec<String>>();
+    assert_eq!(
+      deps.join(","),
+      "react:false,./style.css:false,./foo:false,./lib:false,./mod:true,./static.js:true"
+    );
+  }
+
+  #[test]
+  fn parse_cjs_exports_with_class_static_block() {
+    let source = r#"
+      class Foo {
+        static bar = 1;
+        static {
+          Foo.baz = 2;
+        }
+        static qux() {}
+      }
+      module.exports = Foo;
+		"#;
+    let swc = SWC::parse("index.cjs", source).expect("could not parse module");
+    let (exports, _) = swc
+      .parse_cjs_exports("production", false)
+      .expect("could not parse exports");
+    // known gap: assignments to class members (`Foo.baz = 2`) are not tracked at all, inside a static block or not
+    assert_eq!(exports.join(","), "bar,qux");
+    let swc = SWC::parse(
+      "index.cjs",
+      "class Foo { static bar = 1 } Foo.baz = 2; module.exports = Foo;",
+    )
+    .expect("could not parse module");
+    let (exports, _) = swc
+      .parse_cjs_exports("production", false)
+      .expect("could not parse exports");
+    assert_eq!(exports.join(","), "bar");
+  }
+
+  #[test]
+  fn parse_with_auto_mode() {
+    let swc = SWC::parse_as("index.js", "var await = 1; exports.foo = 1;", ParseMode::Auto).expect("could not parse");
+    assert!(swc.program.is_script());
+    let swc =
+      SWC::parse_as("index.js", "import foo from 'foo'; export { foo };", ParseMode::Auto).expect("could not parse");
+    assert!(swc.program.is_module());
+    let swc =
+      SWC::parse_as("index.js", "const mod = await import('./mod');", ParseMode::Auto).expect("could not parse");
+    assert!(swc.program.is_module());
+    let swc = SWC::parse_as("index.js", "for await (const x of xs) {}", ParseMode::Auto).expect("could not parse");
+    assert!(swc.program.is_module());
+    let swc = SWC::parse_as("index.js", "async function f() { await g(); }", ParseMode::Auto).expect("could not parse");
+    assert!(swc.program.is_script());
+    let swc = SWC::parse_as("index.js", "exports.foo = 1;", ParseMode::Module).expect("could not parse");
+    assert!(swc.program.is_module());
+    assert!(SWC::parse_as("index.js", "import foo from 'foo';", ParseMode::Script).is_err());
+    // a script with top-level `await` is re-parsed as an ES module, which reports the module-only errors
+    let swc = SWC::parse_as("index.js", "with (a) {} await x;", ParseMode::Auto).expect("could not parse");
+    assert!(swc.program.is_module());
+    assert!(swc.diagnostics[0].message.contains("With statement"));
+    let swc = SWC::parse_as("index.js", "var x = 010; await x;", ParseMode::Auto).expect("could not parse");
+    assert!(swc.program.is_module());
+    assert!(swc.diagnostics[0].message.contains("Legacy octal"));
+    // `await` is declared as an identifier, so this is a classic script, though swc still parses `await(1)` as an
+    // await expression
+    let swc = SWC::parse_as("index.js", "function await(x){}; await(1);", ParseMode::Auto).expect("could not parse");
+    assert!(swc.program.is_script());
+  }
+
+  #[test]
+  fn has_top_level_await() {
+    let swc = SWC::parse("index.mjs", "const mod = await import('./mod');").expect("could not parse module");
+    assert!(swc.has_top_level_await());
+    let swc = SWC::parse("index.mjs", "export const data = await fetch(url);").expect("could not parse module");
+    assert!(swc.has_top_level_await());
+    let swc = SWC::parse("index.mjs", "for await (const x of xs) {}").expect("could not parse module");
+    assert!(swc.has_top_level_await());
+    let source = r#"
+      export async function load() { await import('./mod'); }
+      const f = async () => await g();
+      class Foo { async bar() { await g(); } }
+    "#;
+    let swc = SWC::parse("index.mjs", source).expect("could not parse module");
+    assert!(!swc.has_top_level_await());
+    let swc = SWC::parse_script("index.js", "var await = 1;").expect("could not parse script");
+    assert!(!swc.has_top_level_await());
+    let swc = SWC::parse_script("index.js", "function await(x) {}; await(1);").expect("could not parse script");
+    assert!(!swc.has_top_level_await());
+  }
+
+  #[test]
+  fn module_to_json() {
+    let source = r#"
+      const foo = require("foo");
+      exports.bar = foo.bar;
+		"#;
+    let swc = SWC::parse("index.cjs", source).expect("could not parse module");
+    let json = swc.module_to_json().expect("could not serialize module");
+    assert!(json.contains(r#""type":"Module""#), "{}", json);
+    assert!(json.contains(r#""span":{"start":7,"end":34,"ctxt":0}"#), "{}", json);
+    let program: Program = serde_json::from_str(&json).expect("could not deserialize module");
+    assert_eq!(serde_json::to_string(&program).unwrap(), json);
+    let source_map = Rc::new(SourceMap::default());
+    SWC::parse_into(source_map.clone(), "a.cjs", "exports.a = 1;").expect("could not parse module");
+    let swc = SWC::parse_into(source_map, "b.cjs", "exports.b = 1;").expect("could not parse module");
+    let json = swc.module_to_json().expect("could not serialize module");
+    assert!(json.contains(r#""span":{"start":15,"end":29,"ctxt":0}"#), "{}", json);
+  }
 }